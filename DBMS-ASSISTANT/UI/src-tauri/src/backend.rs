@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::db::{self, PooledConnection};
+use crate::policy::{Policy, Rule};
+use crate::python_bridge::{self, ConnectionTarget};
+
+/// Result of a single turn through a `QueryBackend`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryResponse {
+    pub text: String,
+}
+
+/// A pluggable engine for answering a DBA query, selected per connection instead of being
+/// hard-wired into `run_dba_query`. The embedded Python/LLM agent is one implementation; a
+/// backend that runs the query directly as SQL is another, for cases where natural-language
+/// translation would just add latency. `policy` is passed to every backend, not just the ones
+/// that currently use it, so a backend that talks to the pool directly (like `NativeSqlBackend`)
+/// can never skip the same structural enforcement `execute_sql` applies.
+#[async_trait]
+pub trait QueryBackend: Send + Sync {
+    async fn run(
+        &self,
+        query: String,
+        history: Vec<(String, String)>,
+        conn: Option<&PooledConnection>,
+        readonly: bool,
+        policy: &Policy,
+    ) -> Result<QueryResponse, String>;
+}
+
+/// Runs queries through the embedded Python `agent_framework` agent — the original, and still
+/// default, behavior.
+pub struct PythonAgentBackend;
+
+#[async_trait]
+impl QueryBackend for PythonAgentBackend {
+    async fn run(
+        &self,
+        query: String,
+        history: Vec<(String, String)>,
+        conn: Option<&PooledConnection>,
+        readonly: bool,
+        _policy: &Policy,
+    ) -> Result<QueryResponse, String> {
+        let target = conn.map(|c| ConnectionTarget {
+            server: c.server.clone(),
+            database: c.database.clone(),
+        });
+        // The embedded Python turn holds the GIL for its whole duration, so it has to run on a
+        // blocking worker thread rather than inline in this async fn — otherwise it blocks a
+        // tokio worker the same way chunk1-4 fixed for the streaming path.
+        let text = tauri::async_runtime::spawn_blocking(move || {
+            python_bridge::run_python_query_with_history(query, history, readonly, target)
+        })
+        .await
+        .map_err(|e| format!("Query task panicked: {}", e))?
+        .map_err(|e| format!("Python error: {}", e))?;
+        Ok(QueryResponse { text })
+    }
+}
+
+/// Runs `query` directly as SQL against the connected database, with no LLM round-trip — for
+/// callers that already know the exact statement they want to execute. Bypassing the agent also
+/// bypasses the MCP tool's policy check, so this backend has to apply `Policy::evaluate` itself
+/// before it ever touches the pool.
+pub struct NativeSqlBackend;
+
+#[async_trait]
+impl QueryBackend for NativeSqlBackend {
+    async fn run(
+        &self,
+        query: String,
+        _history: Vec<(String, String)>,
+        conn: Option<&PooledConnection>,
+        _readonly: bool,
+        policy: &Policy,
+    ) -> Result<QueryResponse, String> {
+        match policy.evaluate(&query) {
+            Rule::Deny => {
+                return Err(format!("This statement's class is denied by the current policy: {}", query))
+            }
+            Rule::RequireConfirmation => {
+                return Err(format!("This statement requires confirmation before it runs: {}", query))
+            }
+            Rule::Allow => {}
+        }
+
+        let conn = conn.ok_or_else(|| "Not connected to a database".to_string())?;
+        let rows = db::execute_sql(&conn.pool, &query).await?;
+        let text = serde_json::to_string_pretty(&rows).map_err(|e| format!("Failed to serialize rows: {}", e))?;
+        Ok(QueryResponse { text })
+    }
+}
+
+/// Which `QueryBackend` a connection should use, chosen at connect time and carried alongside
+/// the pool in `PooledConnection`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    PythonAgent,
+    NativeSql,
+}
+
+impl Default for BackendKind {
+    fn default() -> Self {
+        BackendKind::PythonAgent
+    }
+}
+
+impl BackendKind {
+    pub fn build(self) -> Box<dyn QueryBackend> {
+        match self {
+            BackendKind::PythonAgent => Box::new(PythonAgentBackend),
+            BackendKind::NativeSql => Box::new(NativeSqlBackend),
+        }
+    }
+}
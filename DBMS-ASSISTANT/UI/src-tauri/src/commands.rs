@@ -1,33 +1,101 @@
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
-use tauri::State;
-use crate::python_bridge;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{ipc::Channel, State};
+use crate::backend::BackendKind;
+use crate::credentials::{self, ConnectionProfile, CredentialVault};
+use crate::db::{self, ConnectError, PooledConnection, SchemaSnapshot};
+use crate::history::{self, HistoryEntry, HistoryStats, HistoryStore};
+use crate::policy::{self, Policy, Rule};
+use crate::python_bridge::{self, ConnectionTarget, StreamEvent};
+use crate::result_cache::ResultCache;
+use crate::schema_index::{self, SchemaEntry, SchemaIndex};
 
 // Application state
 pub struct AppState {
-    pub is_connected: Mutex<bool>,
-    pub server_name: Mutex<Option<String>>,
-    pub database_name: Mutex<Option<String>>,
+    /// Live connections keyed by the alias the user supplied when connecting, so several
+    /// servers/databases can stay open at once instead of one global connection clobbering
+    /// the last.
+    pub connections: Mutex<HashMap<String, PooledConnection>>,
+    /// Alias used when a command doesn't specify one explicitly.
+    pub active_connection: Mutex<Option<String>>,
     pub conversation_history: Mutex<Vec<(String, String)>>, // (user_query, assistant_response)
+    pub result_cache: ResultCache,
+    pub schema_index: Mutex<Option<SchemaIndex>>,
+    pub policy: Mutex<Policy>,
+    pub history_store: HistoryStore,
+    pub credential_vault: CredentialVault,
+    /// Cancellation flags for in-flight `run_dba_query_stream` calls, keyed by request id, so
+    /// several streaming queries can run concurrently and be cancelled independently.
+    pub active_queries: Mutex<HashMap<String, Arc<AtomicBool>>>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
-            is_connected: Mutex::new(false),
-            server_name: Mutex::new(None),
-            database_name: Mutex::new(None),
+            connections: Mutex::new(HashMap::new()),
+            active_connection: Mutex::new(None),
             conversation_history: Mutex::new(Vec::new()),
+            active_queries: Mutex::new(HashMap::new()),
+            result_cache: ResultCache::default(),
+            schema_index: Mutex::new(SchemaIndex::load(&schema_index::default_index_path())),
+            policy: Mutex::new(Policy::load(&policy::default_policy_path())),
+            history_store: HistoryStore::open(&history::default_history_path()).unwrap_or_else(|e| {
+                tracing::error!(error = %e, "failed to open history store; falling back to in-memory");
+                HistoryStore::open_in_memory().expect("failed to open in-memory history store")
+            }),
+            credential_vault: CredentialVault::open(&credentials::default_vault_path()).unwrap_or_else(|e| {
+                tracing::error!(error = %e, "failed to open credential vault; falling back to in-memory");
+                CredentialVault::open_in_memory().expect("failed to open in-memory credential vault")
+            }),
         }
     }
 }
 
+impl AppState {
+    /// Resolve `connection` (an alias) to its pooled connection, falling back to the active
+    /// connection when `None`.
+    fn resolve_connection(&self, connection: &Option<String>) -> Result<PooledConnection, String> {
+        let connections = self.connections.lock().unwrap();
+        let alias = connection
+            .clone()
+            .or_else(|| self.active_connection.lock().unwrap().clone())
+            .ok_or_else(|| "Not connected to a database".to_string())?;
+
+        connections
+            .get(&alias)
+            .cloned()
+            .ok_or_else(|| format!("No live connection named '{}'", alias))
+    }
+}
+
 // Data structures
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConnectionInfo {
+    /// User-supplied name for this connection, e.g. "prod" or "reporting-replica".
+    pub alias: String,
     pub server: String,
     pub database: String,
     pub username: Option<String>,
+    pub password: Option<String>,
+    /// Require an encrypted (TLS) session instead of a plaintext TDS connection.
+    #[serde(default)]
+    pub encrypt: bool,
+    /// Accept the server's certificate without validating it against a trusted CA. Ignored
+    /// when `ca_cert_path` is set.
+    #[serde(default)]
+    pub trust_server_cert: bool,
+    /// PEM-encoded CA certificate to validate the server's certificate against.
+    #[serde(default)]
+    pub ca_cert_path: Option<String>,
+    #[serde(default)]
+    pub connect_timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub application_name: Option<String>,
+    /// Which `QueryBackend` queries against this connection should run through.
+    #[serde(default)]
+    pub backend: BackendKind,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -39,10 +107,20 @@ pub struct QueryResult {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ConnectionStatus {
-    pub is_connected: bool,
-    pub server: Option<String>,
-    pub database: Option<String>,
+pub struct ConnectionEntry {
+    pub alias: String,
+    pub server: String,
+    pub database: String,
+}
+
+/// Outcome of running a statement through the policy engine: either it ran and returned rows,
+/// or it was stopped structurally instead of merely being discouraged in a prompt.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SqlExecutionResult {
+    Executed { rows: Vec<serde_json::Value> },
+    NeedsConfirmation { reason: String },
+    Denied { reason: String },
 }
 
 // Tauri Commands
@@ -53,23 +131,145 @@ pub fn greet(name: &str) -> String {
 }
 
 #[tauri::command]
-pub async fn run_dba_query(query: String, state: State<'_, AppState>) -> Result<QueryResult, String> {
+pub async fn run_dba_query(
+    query: String,
+    connection: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<QueryResult, String> {
     let start = std::time::Instant::now();
-    
+
     // Get conversation history
     let history = state.conversation_history.lock().unwrap().clone();
-    
-    // Call Python bridge with history
-    let result = match python_bridge::run_python_query_with_history(query.clone(), history) {
+    let policy = state.policy.lock().unwrap().clone();
+    let readonly = policy.is_readonly();
+    let conn = state.resolve_connection(&connection).ok();
+    let backend = conn.as_ref().map(|c| c.backend).unwrap_or_default().build();
+
+    // Dispatch through whichever backend this connection (or the default) is configured for,
+    // instead of calling python_bridge directly. Every backend gets the loaded policy, not just
+    // the ones that currently consult it, so none of them can bypass it structurally.
+    let response = match backend.run(query.clone(), history, conn.as_ref(), readonly, &policy).await {
+        Ok(response) => response.text,
+        Err(e) => {
+            let elapsed = start.elapsed().as_millis() as u64;
+            let _ = state.history_store.record_turn(connection.as_deref(), &query, &e, elapsed, false);
+            return Err(e);
+        }
+    };
+
+    // Save to conversation history
+    state.conversation_history.lock().unwrap().push((query.clone(), response.clone()));
+
+    let elapsed = start.elapsed().as_millis() as u64;
+    let _ = state.history_store.record_turn(connection.as_deref(), &query, &response, elapsed, true);
+
+    Ok(QueryResult {
+        success: true,
+        message: "Query executed successfully".to_string(),
+        data: Some(response),
+        execution_time_ms: elapsed,
+    })
+}
+
+/// Run a request through the Planner/SQL-Executor/Diagram team instead of the single
+/// monolithic agent, for complex multi-step tasks that need deterministic dispatch rather than
+/// one agent juggling every role via a giant behavioral-rules prompt.
+#[tauri::command]
+pub async fn run_orchestrated_query(
+    query: String,
+    connection: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<QueryResult, String> {
+    let start = std::time::Instant::now();
+
+    let history = state.conversation_history.lock().unwrap().clone();
+    let readonly = state.policy.lock().unwrap().is_readonly();
+    let target = state.resolve_connection(&connection).ok().map(|c| ConnectionTarget {
+        server: c.server,
+        database: c.database,
+    });
+
+    let result = match python_bridge::run_orchestrated_query(query.clone(), history, readonly, target) {
         Ok(response) => response,
         Err(e) => return Err(format!("Python error: {}", e)),
     };
-    
-    // Save to conversation history
+
     state.conversation_history.lock().unwrap().push((query, result.clone()));
-    
+
+    let elapsed = start.elapsed().as_millis() as u64;
+
+    Ok(QueryResult {
+        success: true,
+        message: "Orchestrated query executed successfully".to_string(),
+        data: Some(result),
+        execution_time_ms: elapsed,
+    })
+}
+
+/// Streaming variant of `run_dba_query`: emits each text chunk and tool call over `channel`
+/// as the agent produces them, then resolves with the final assembled result. Runs on a
+/// blocking worker task (the embedded Python turn doesn't yield) so several streaming queries
+/// can be in flight at once, each tracked in `AppState.active_queries` by `request_id` so
+/// `cancel_query` can stop forwarding its events mid-turn.
+#[tauri::command]
+pub async fn run_dba_query_stream(
+    request_id: String,
+    query: String,
+    connection: Option<String>,
+    state: State<'_, AppState>,
+    channel: Channel<StreamEvent>,
+) -> Result<QueryResult, String> {
+    let start = std::time::Instant::now();
+
+    let history = state.conversation_history.lock().unwrap().clone();
+    let readonly = state.policy.lock().unwrap().is_readonly();
+    let target = state.resolve_connection(&connection).ok().map(|c| ConnectionTarget {
+        server: c.server,
+        database: c.database,
+    });
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    state.active_queries.lock().unwrap().insert(request_id.clone(), cancelled.clone());
+
+    let query_for_task = query.clone();
+    let cancelled_for_events = cancelled.clone();
+    let task_result = tauri::async_runtime::spawn_blocking(move || {
+        python_bridge::run_python_query_stream(
+            query_for_task,
+            history,
+            move |event| {
+                if !cancelled_for_events.load(Ordering::Relaxed) {
+                    let _ = channel.send(event);
+                }
+            },
+            readonly,
+            target,
+        )
+    })
+    .await;
+
+    // Remove the cancellation flag before handling the task's outcome, so a panicking task
+    // can't leak its `request_id` in `active_queries` forever.
+    state.active_queries.lock().unwrap().remove(&request_id);
+    let task_result = task_result.map_err(|e| format!("Streaming task panicked: {}", e))?;
     let elapsed = start.elapsed().as_millis() as u64;
-    
+
+    if cancelled.load(Ordering::Relaxed) {
+        return Ok(QueryResult {
+            success: false,
+            message: "Query cancelled".to_string(),
+            data: None,
+            execution_time_ms: elapsed,
+        });
+    }
+
+    let result = match task_result {
+        Ok(response) => response,
+        Err(e) => return Err(format!("Python error: {}", e)),
+    };
+
+    state.conversation_history.lock().unwrap().push((query, result.clone()));
+
     Ok(QueryResult {
         success: true,
         message: "Query executed successfully".to_string(),
@@ -78,35 +278,241 @@ pub async fn run_dba_query(query: String, state: State<'_, AppState>) -> Result<
     })
 }
 
+/// Request cancellation of an in-flight `run_dba_query_stream` call by its request id. The
+/// embedded Python turn keeps running to completion, but no further events are forwarded to
+/// the frontend, and once the turn finishes the streaming command resolves with a cancelled
+/// result instead of the response.
 #[tauri::command]
-pub fn clear_conversation(state: State<'_, AppState>) -> String {
+pub fn cancel_query(request_id: String, state: State<'_, AppState>) -> Result<String, String> {
+    match state.active_queries.lock().unwrap().get(&request_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            Ok(format!("Cancellation requested for '{}'", request_id))
+        }
+        None => Err(format!("No in-flight query with id '{}'", request_id)),
+    }
+}
+
+#[tauri::command]
+pub fn clear_conversation(state: State<'_, AppState>) -> Result<String, String> {
     state.conversation_history.lock().unwrap().clear();
-    "Conversation cleared".to_string()
+    state.history_store.clear()?;
+    Ok("Conversation cleared".to_string())
+}
+
+/// Full-text search over persisted conversation history, optionally scoped to one connection.
+#[tauri::command]
+pub fn search_history(
+    query: String,
+    limit: usize,
+    connection_alias: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<HistoryEntry>, String> {
+    state.history_store.search(&query, limit, connection_alias.as_deref())
+}
+
+/// Turn counts and average execution time across all persisted history.
+#[tauri::command]
+pub fn history_stats(state: State<'_, AppState>) -> Result<HistoryStats, String> {
+    state.history_store.stats()
 }
 
 #[tauri::command]
 pub async fn connect_database(
     connection_info: ConnectionInfo,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    // TODO: Integrate with Python agent to establish connection
-    
-    // Update state
-    *state.is_connected.lock().unwrap() = true;
-    *state.server_name.lock().unwrap() = Some(connection_info.server.clone());
-    *state.database_name.lock().unwrap() = Some(connection_info.database.clone());
-    
+) -> Result<String, ConnectError> {
+    let options = db::ConnectOptions {
+        encrypt: connection_info.encrypt,
+        trust_server_cert: connection_info.trust_server_cert,
+        ca_cert_path: connection_info.ca_cert_path.clone(),
+        connect_timeout_ms: connection_info.connect_timeout_ms,
+        application_name: connection_info.application_name.clone(),
+    };
+
+    let pool = db::connect_pool(
+        &connection_info.server,
+        &connection_info.database,
+        connection_info.username.as_deref(),
+        connection_info.password.as_deref(),
+        options,
+    )
+    .await?;
+
+    let alias = connection_info.alias.clone();
+    state.connections.lock().unwrap().insert(
+        alias.clone(),
+        PooledConnection {
+            pool,
+            server: connection_info.server.clone(),
+            database: connection_info.database.clone(),
+            backend: connection_info.backend,
+        },
+    );
+    *state.active_connection.lock().unwrap() = Some(alias);
+
     Ok(format!(
         "Connected to {}.{}",
         connection_info.server, connection_info.database
     ))
 }
 
+/// Encrypt `password` under the app passphrase and save the rest of `connection_info`
+/// alongside it, so the user can reconnect later via `connect_profile` without retyping or
+/// exposing the secret. `AppState` never holds this password in plaintext.
+#[tauri::command]
+pub fn save_connection_profile(
+    alias: String,
+    connection_info: ConnectionInfo,
+    password: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let passphrase = credentials::vault_passphrase()?;
+    state.credential_vault.save_profile(
+        &alias,
+        &connection_info.server,
+        &connection_info.database,
+        connection_info.username.as_deref(),
+        &password,
+        &passphrase,
+        connection_info.encrypt,
+        connection_info.trust_server_cert,
+        connection_info.ca_cert_path.as_deref(),
+        connection_info.connect_timeout_ms,
+        connection_info.application_name.as_deref(),
+    )?;
+    Ok(format!("Saved connection profile '{}'", alias))
+}
+
+/// List saved connection profiles (never including passwords).
+#[tauri::command]
+pub fn list_profiles(state: State<'_, AppState>) -> Result<Vec<ConnectionProfile>, String> {
+    state.credential_vault.list_profiles()
+}
+
+/// Decrypt the saved password for `alias` using the same app-wide vault passphrase
+/// `save_connection_profile` encrypted it under, and connect with it.
+#[tauri::command]
+pub async fn connect_profile(
+    alias: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let connection_info = state.credential_vault.load_profile(&alias, &credentials::vault_passphrase()?)?;
+    connect_database(connection_info, state).await.map_err(|e| e.to_string())
+}
+
+/// List every live connection, e.g. to populate a connection switcher in the UI.
+#[tauri::command]
+pub fn get_connection_status(state: State<'_, AppState>) -> Vec<ConnectionEntry> {
+    state
+        .connections
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(alias, conn)| ConnectionEntry {
+            alias: alias.clone(),
+            server: conn.server.clone(),
+            database: conn.database.clone(),
+        })
+        .collect()
+}
+
+/// Run the deterministic schema-discovery queries natively instead of relying on the agent
+/// to fetch them over the MCP tool one call at a time.
 #[tauri::command]
-pub fn get_connection_status(state: State<'_, AppState>) -> ConnectionStatus {
-    ConnectionStatus {
-        is_connected: *state.is_connected.lock().unwrap(),
-        server: state.server_name.lock().unwrap().clone(),
-        database: state.database_name.lock().unwrap().clone(),
+pub async fn introspect_schema(
+    connection: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SchemaSnapshot, String> {
+    let conn = state.resolve_connection(&connection)?;
+
+    let snapshot = db::introspect_schema(&conn.pool).await?;
+
+    // Schema drift invalidates any previously embedded index; rebuild it from this scan so
+    // `search_schema` never serves stale tables/columns.
+    let index_path = schema_index::default_index_path();
+    SchemaIndex::invalidate(&index_path);
+    *state.schema_index.lock().unwrap() = None;
+    let index = SchemaIndex::build(&snapshot, index_path).await?;
+    *state.schema_index.lock().unwrap() = Some(index);
+
+    Ok(snapshot)
+}
+
+/// Return the tables/columns most relevant to `natural_language`, retrieved from the embedded
+/// schema index instead of handing the agent the entire catalog.
+#[tauri::command]
+pub async fn search_schema(
+    natural_language: String,
+    top_k: usize,
+    state: State<'_, AppState>,
+) -> Result<Vec<SchemaEntry>, String> {
+    let index_path = schema_index::default_index_path();
+    let index_guard = state.schema_index.lock().unwrap().take();
+    let index = match index_guard {
+        Some(index) => index,
+        None => SchemaIndex::load(&index_path).ok_or_else(|| "No schema index available; run introspect_schema first".to_string())?,
+    };
+
+    let results = index.search(&natural_language, top_k).await;
+    *state.schema_index.lock().unwrap() = Some(index);
+    results
+}
+
+/// Classify `sql` against the loaded policy before it is run on the native pool: `deny`
+/// classes are rejected outright, `require-confirmation` classes are handed back to the UI
+/// instead of being run, and only `allow` classes are executed.
+#[tauri::command]
+pub async fn execute_sql(
+    sql: String,
+    connection: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SqlExecutionResult, String> {
+    let rule = state.policy.lock().unwrap().evaluate(&sql);
+
+    match rule {
+        Rule::Deny => Ok(SqlExecutionResult::Denied {
+            reason: format!("This statement's class is denied by the current policy: {}", sql),
+        }),
+        Rule::RequireConfirmation => Ok(SqlExecutionResult::NeedsConfirmation {
+            reason: format!("This statement requires confirmation before it runs: {}", sql),
+        }),
+        Rule::Allow => {
+            let conn = state.resolve_connection(&connection)?;
+            let rows = db::execute_sql(&conn.pool, &sql).await?;
+            Ok(SqlExecutionResult::Executed { rows })
+        }
     }
 }
+
+#[tauri::command]
+pub fn set_policy(policy: Policy, state: State<'_, AppState>) {
+    *state.policy.lock().unwrap() = policy;
+}
+
+#[tauri::command]
+pub fn get_policy(state: State<'_, AppState>) -> Policy {
+    state.policy.lock().unwrap().clone()
+}
+
+/// Register a result set (rows returned from a prior query) as an in-memory table the agent
+/// can assign a name to, so follow-up questions can filter/aggregate/join it locally via
+/// `query_cached_results` instead of triggering another LLM + SQL round-trip.
+#[tauri::command]
+pub async fn register_result_set(
+    name: String,
+    rows: Vec<serde_json::Value>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    state.result_cache.register_result_set(&name, rows).await?;
+    Ok(format!("Registered result set '{}'", name))
+}
+
+/// Run plain SQL over previously registered result sets and return the rows as JSON.
+#[tauri::command]
+pub async fn query_cached_results(
+    sql: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<serde_json::Value>, String> {
+    state.result_cache.query(&sql).await
+}
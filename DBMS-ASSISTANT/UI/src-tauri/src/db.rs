@@ -0,0 +1,384 @@
+use bb8::Pool;
+use bb8_tiberius::ConnectionManager;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tiberius::{AuthMethod, Config, EncryptionLevel};
+use uuid::Uuid;
+
+use crate::backend::BackendKind;
+
+/// Pooled connection handle kept in `AppState` so repeated queries reuse live sessions
+/// instead of paying a fresh TCP + login round-trip every time.
+pub type DbPool = Pool<ConnectionManager>;
+
+/// A live, named connection the user can keep open alongside others. Tracked by the alias
+/// they supplied when connecting, so `run_dba_query` and friends can route a query to a
+/// specific server/database instead of clobbering a single global connection string.
+#[derive(Clone)]
+pub struct PooledConnection {
+    pub pool: DbPool,
+    pub server: String,
+    pub database: String,
+    /// Which `QueryBackend` queries against this connection should run through.
+    pub backend: BackendKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnInfo {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub data_type: String,
+    pub is_nullable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrimaryKeyInfo {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub key_ordinal: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignKeyInfo {
+    pub name: String,
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub referenced_schema: String,
+    pub referenced_table: String,
+    pub referenced_column: String,
+}
+
+/// Result of a full schema scan: everything the agent used to have to ask the LLM to
+/// rediscover turn after turn.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchemaSnapshot {
+    pub tables: Vec<String>,
+    pub columns: Vec<ColumnInfo>,
+    pub primary_keys: Vec<PrimaryKeyInfo>,
+    pub foreign_keys: Vec<ForeignKeyInfo>,
+}
+
+/// Transport and timeout options for `connect_pool`, mirroring the corresponding fields on
+/// `ConnectionInfo`.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectOptions {
+    /// Require an encrypted (TLS) session instead of a plaintext TDS connection.
+    pub encrypt: bool,
+    /// Accept the server's certificate without validating it against a trusted CA. Ignored
+    /// when `ca_cert_path` is set.
+    pub trust_server_cert: bool,
+    /// PEM-encoded CA certificate to validate the server's certificate against.
+    pub ca_cert_path: Option<String>,
+    pub connect_timeout_ms: Option<u64>,
+    pub application_name: Option<String>,
+}
+
+/// Why a connection attempt failed, coarse enough for the frontend to react differently —
+/// e.g. offer `trust_server_cert` on a TLS failure vs. prompting for new credentials on an
+/// auth failure — without having to parse driver error text itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConnectError {
+    Auth { message: String },
+    Tls { message: String },
+    Timeout { message: String },
+    Other { message: String },
+}
+
+impl ConnectError {
+    /// Classify a driver error by its message. Tiberius doesn't expose a structured
+    /// auth/TLS/timeout distinction, so this is a best-effort read of the error text.
+    fn classify(message: String) -> Self {
+        let lower = message.to_ascii_lowercase();
+        if lower.contains("login failed") || lower.contains("authentication") || lower.contains("password") {
+            ConnectError::Auth { message }
+        } else if lower.contains("tls") || lower.contains("certificate") || lower.contains("ssl") {
+            ConnectError::Tls { message }
+        } else if lower.contains("timed out") || lower.contains("timeout") {
+            ConnectError::Timeout { message }
+        } else {
+            ConnectError::Other { message }
+        }
+    }
+}
+
+impl std::fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (ConnectError::Auth { message }
+        | ConnectError::Tls { message }
+        | ConnectError::Timeout { message }
+        | ConnectError::Other { message }) = self;
+        write!(f, "{}", message)
+    }
+}
+
+/// Open a pooled connection to a SQL Server instance. Falls back to SQL auth when
+/// `username`/`password` are supplied, otherwise uses the caller's Windows integrated identity.
+pub async fn connect_pool(
+    server: &str,
+    database: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+    options: ConnectOptions,
+) -> Result<DbPool, ConnectError> {
+    let mut config = Config::new();
+    config.host(server);
+    config.database(database);
+
+    config.encryption(if options.encrypt {
+        EncryptionLevel::Required
+    } else {
+        EncryptionLevel::NotSupported
+    });
+
+    match &options.ca_cert_path {
+        Some(path) => config.trust_cert_ca(path.clone()),
+        None if options.trust_server_cert => config.trust_cert(),
+        None => {}
+    }
+
+    if let Some(name) = &options.application_name {
+        config.application_name(name);
+    }
+
+    match (username, password) {
+        (Some(user), Some(pass)) => config.authentication(AuthMethod::sql_server(user, pass)),
+        _ => config.authentication(AuthMethod::Integrated),
+    };
+
+    let manager = ConnectionManager::new(config);
+    let build = Pool::builder().max_size(5).build(manager);
+
+    let pool = match options.connect_timeout_ms {
+        Some(ms) => tokio::time::timeout(std::time::Duration::from_millis(ms), build)
+            .await
+            .map_err(|_| ConnectError::Timeout {
+                message: format!("Connection to {} timed out after {}ms", server, ms),
+            })?,
+        None => build.await,
+    };
+
+    pool.map_err(|e| ConnectError::classify(format!("Failed to build connection pool: {}", e)))
+}
+
+/// Run the deterministic `INFORMATION_SCHEMA` / `sys.foreign_keys` discovery queries
+/// directly against SQL Server instead of instructing the agent to do it one call at a time.
+pub async fn introspect_schema(pool: &DbPool) -> Result<SchemaSnapshot, String> {
+    let mut conn = pool
+        .get()
+        .await
+        .map_err(|e| format!("Failed to check out connection: {}", e))?;
+
+    let table_rows = conn
+        .query(
+            "SELECT TABLE_SCHEMA, TABLE_NAME FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_TYPE = 'BASE TABLE' ORDER BY TABLE_SCHEMA, TABLE_NAME",
+            &[],
+        )
+        .await
+        .map_err(|e| format!("Failed to list tables: {}", e))?
+        .into_first_result()
+        .await
+        .map_err(|e| format!("Failed to read table list: {}", e))?;
+
+    let tables: Vec<String> = table_rows
+        .iter()
+        .map(|row| {
+            let schema: &str = row.get("TABLE_SCHEMA").unwrap_or_default();
+            let table: &str = row.get("TABLE_NAME").unwrap_or_default();
+            format!("{}.{}", schema, table)
+        })
+        .collect();
+
+    let column_rows = conn
+        .query(
+            "SELECT TABLE_SCHEMA, TABLE_NAME, COLUMN_NAME, DATA_TYPE, IS_NULLABLE FROM INFORMATION_SCHEMA.COLUMNS ORDER BY TABLE_SCHEMA, TABLE_NAME, ORDINAL_POSITION",
+            &[],
+        )
+        .await
+        .map_err(|e| format!("Failed to list columns: {}", e))?
+        .into_first_result()
+        .await
+        .map_err(|e| format!("Failed to read column list: {}", e))?;
+
+    let columns: Vec<ColumnInfo> = column_rows
+        .iter()
+        .map(|row| ColumnInfo {
+            schema: row.get::<&str, _>("TABLE_SCHEMA").unwrap_or_default().to_string(),
+            table: row.get::<&str, _>("TABLE_NAME").unwrap_or_default().to_string(),
+            column: row.get::<&str, _>("COLUMN_NAME").unwrap_or_default().to_string(),
+            data_type: row.get::<&str, _>("DATA_TYPE").unwrap_or_default().to_string(),
+            is_nullable: row.get::<&str, _>("IS_NULLABLE").unwrap_or_default() == "YES",
+        })
+        .collect();
+
+    let pk_rows = conn
+        .query(
+            "SELECT OBJECT_SCHEMA_NAME(t.object_id) AS Schema_Name, t.name AS Table_Name, c.name AS Column_Name, ic.key_ordinal AS Key_Order \
+             FROM sys.tables t \
+             INNER JOIN sys.indexes i ON t.object_id = i.object_id \
+             INNER JOIN sys.index_columns ic ON i.object_id = ic.object_id AND i.index_id = ic.index_id \
+             INNER JOIN sys.columns c ON ic.object_id = c.object_id AND ic.column_id = c.column_id \
+             WHERE i.is_primary_key = 1 \
+             ORDER BY Table_Name, Key_Order",
+            &[],
+        )
+        .await
+        .map_err(|e| format!("Failed to list primary keys: {}", e))?
+        .into_first_result()
+        .await
+        .map_err(|e| format!("Failed to read primary key list: {}", e))?;
+
+    let primary_keys: Vec<PrimaryKeyInfo> = pk_rows
+        .iter()
+        .map(|row| PrimaryKeyInfo {
+            schema: row.get::<&str, _>("Schema_Name").unwrap_or_default().to_string(),
+            table: row.get::<&str, _>("Table_Name").unwrap_or_default().to_string(),
+            column: row.get::<&str, _>("Column_Name").unwrap_or_default().to_string(),
+            key_ordinal: row.get("Key_Order").unwrap_or_default(),
+        })
+        .collect();
+
+    let fk_rows = conn
+        .query(
+            "SELECT fk.name AS FK_Name, OBJECT_SCHEMA_NAME(fk.parent_object_id) AS Schema_Name, OBJECT_NAME(fk.parent_object_id) AS Table_Name, \
+             COL_NAME(fkc.parent_object_id, fkc.parent_column_id) AS Column_Name, OBJECT_SCHEMA_NAME(fk.referenced_object_id) AS Referenced_Schema, \
+             OBJECT_NAME(fk.referenced_object_id) AS Referenced_Table, COL_NAME(fkc.referenced_object_id, fkc.referenced_column_id) AS Referenced_Column \
+             FROM sys.foreign_keys AS fk \
+             INNER JOIN sys.foreign_key_columns AS fkc ON fk.object_id = fkc.constraint_object_id \
+             ORDER BY Schema_Name, Table_Name, FK_Name",
+            &[],
+        )
+        .await
+        .map_err(|e| format!("Failed to list foreign keys: {}", e))?
+        .into_first_result()
+        .await
+        .map_err(|e| format!("Failed to read foreign key list: {}", e))?;
+
+    let foreign_keys: Vec<ForeignKeyInfo> = fk_rows
+        .iter()
+        .map(|row| ForeignKeyInfo {
+            name: row.get::<&str, _>("FK_Name").unwrap_or_default().to_string(),
+            schema: row.get::<&str, _>("Schema_Name").unwrap_or_default().to_string(),
+            table: row.get::<&str, _>("Table_Name").unwrap_or_default().to_string(),
+            column: row.get::<&str, _>("Column_Name").unwrap_or_default().to_string(),
+            referenced_schema: row.get::<&str, _>("Referenced_Schema").unwrap_or_default().to_string(),
+            referenced_table: row.get::<&str, _>("Referenced_Table").unwrap_or_default().to_string(),
+            referenced_column: row.get::<&str, _>("Referenced_Column").unwrap_or_default().to_string(),
+        })
+        .collect();
+
+    Ok(SchemaSnapshot {
+        tables,
+        columns,
+        primary_keys,
+        foreign_keys,
+    })
+}
+
+/// Reject a SQL string that contains a semicolon anywhere but at its end. SQL Server's
+/// `simple_query` happily executes every semicolon-separated statement in a batch, which would
+/// let something like `"SELECT 1; DROP TABLE Users;"` slip past `StatementClass::classify`
+/// (which only looks at the first statement's leading keyword) and still run the `DROP`. This
+/// is a plain character check, not a parse: statements that T-SQL runs back to back without a
+/// separating semicolon are not detected, and a semicolon inside a string literal or comment
+/// is rejected like a separator.
+fn ensure_single_statement(sql: &str) -> Result<(), String> {
+    let trimmed = sql.trim().trim_end_matches(';').trim();
+    if trimmed.contains(';') {
+        return Err(
+            "Only a single SQL statement is allowed per execution; semicolon-separated batches are rejected"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Execute a statement that has already cleared the policy engine and return its rows as
+/// JSON. Column values are read as the common scalar types tiberius exposes — strings,
+/// integers, floats, bools, `DECIMAL`/`NUMERIC`/`MONEY`, `DATETIME2`, `UNIQUEIDENTIFIER`, and
+/// `VARBINARY` (rendered as a `0x`-prefixed hex string) — so real values like a `money` column
+/// survive into the DataFusion cache instead of silently becoming JSON `null`.
+pub async fn execute_sql(pool: &DbPool, sql: &str) -> Result<Vec<Value>, String> {
+    ensure_single_statement(sql)?;
+
+    let mut conn = pool
+        .get()
+        .await
+        .map_err(|e| format!("Failed to check out connection: {}", e))?;
+
+    let rows = conn
+        .simple_query(sql)
+        .await
+        .map_err(|e| format!("Query failed: {}", e))?
+        .into_first_result()
+        .await
+        .map_err(|e| format!("Failed to read query results: {}", e))?;
+
+    let json_rows = rows
+        .iter()
+        .map(|row| {
+            let mut obj = serde_json::Map::new();
+            for column in row.columns() {
+                let name = column.name();
+                // `try_get` rather than `get`: `get` panics when the column's type doesn't match
+                // the requested one, which would abort on the first non-string column here.
+                let value = row
+                    .try_get::<&str, _>(name).ok().flatten()
+                    .map(Value::from)
+                    .or_else(|| row.try_get::<i64, _>(name).ok().flatten().map(Value::from))
+                    .or_else(|| row.try_get::<i32, _>(name).ok().flatten().map(Value::from))
+                    .or_else(|| row.try_get::<f64, _>(name).ok().flatten().map(Value::from))
+                    .or_else(|| row.try_get::<bool, _>(name).ok().flatten().map(Value::from))
+                    .or_else(|| row.try_get::<Decimal, _>(name).ok().flatten().map(|d| Value::from(d.to_string())))
+                    .or_else(|| row.try_get::<NaiveDateTime, _>(name).ok().flatten().map(|dt| Value::from(dt.to_string())))
+                    .or_else(|| row.try_get::<Uuid, _>(name).ok().flatten().map(|u| Value::from(u.to_string())))
+                    .or_else(|| row.try_get::<&[u8], _>(name).ok().flatten().map(|bytes| Value::from(to_hex_string(bytes))))
+                    .unwrap_or(Value::Null);
+                obj.insert(name.to_string(), value);
+            }
+            Value::Object(obj)
+        })
+        .collect();
+
+    Ok(json_rows)
+}
+
+/// Render `VARBINARY` bytes as a `0x`-prefixed hex string, matching how SQL Server itself
+/// prints binary literals.
+fn to_hex_string(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 + bytes.len() * 2);
+    out.push_str("0x");
+    for byte in bytes {
+        out.push_str(&format!("{:02x}", byte));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_a_single_statement_with_or_without_trailing_semicolon() {
+        assert!(ensure_single_statement("SELECT 1").is_ok());
+        assert!(ensure_single_statement("SELECT 1;").is_ok());
+        assert!(ensure_single_statement("  SELECT 1 ;  ").is_ok());
+    }
+
+    #[test]
+    fn rejects_a_semicolon_separated_batch() {
+        assert!(ensure_single_statement("SELECT 1; DROP TABLE Users;").is_err());
+        assert!(ensure_single_statement("SELECT 1; SELECT 2").is_err());
+    }
+
+    #[test]
+    fn hex_encodes_varbinary() {
+        assert_eq!(to_hex_string(&[0xDE, 0xAD, 0xBE, 0xEF]), "0xdeadbeef");
+        assert_eq!(to_hex_string(&[]), "0x");
+    }
+}
@@ -0,0 +1,171 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// One recorded conversation turn, as returned by `search_history`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub connection_alias: Option<String>,
+    pub user_query: String,
+    pub assistant_response: String,
+    pub execution_time_ms: u64,
+    pub success: bool,
+}
+
+/// Aggregate counters returned by `history_stats`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryStats {
+    pub total_turns: i64,
+    pub successful_turns: i64,
+    pub average_execution_time_ms: f64,
+}
+
+/// Durable, full-text-searchable conversation history backed by a SQLite file under the
+/// DBMS-ASSISTANT directory (see `default_history_path`), so turns survive restarts instead of
+/// living only in `AppState`'s in-memory vector.
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+/// Default on-disk location for the history database: `history.sqlite3` under the
+/// DBMS-ASSISTANT directory, alongside `schema_index.bin` and `.env`.
+pub fn default_history_path() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.pop(); // Remove "src-tauri"
+    path.pop(); // Remove "UI"
+    path.push("history.sqlite3");
+    path
+}
+
+/// Wrap every whitespace-separated term of `query` in its own quoted FTS5 phrase, so ordinary
+/// search terms containing FTS5 query syntax (`"on-call"`, `SELECT *`, unbalanced quotes) are
+/// matched literally instead of throwing an FTS5 syntax error.
+fn sanitize_fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp TEXT NOT NULL,
+    connection_alias TEXT,
+    user_query TEXT NOT NULL,
+    assistant_response TEXT NOT NULL,
+    execution_time_ms INTEGER NOT NULL,
+    success INTEGER NOT NULL
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS history_fts USING fts5(
+    user_query, assistant_response, content='history', content_rowid='id'
+);
+CREATE TRIGGER IF NOT EXISTS history_ai AFTER INSERT ON history BEGIN
+    INSERT INTO history_fts(rowid, user_query, assistant_response) VALUES (new.id, new.user_query, new.assistant_response);
+END;
+CREATE TRIGGER IF NOT EXISTS history_ad AFTER DELETE ON history BEGIN
+    INSERT INTO history_fts(history_fts, rowid, user_query, assistant_response) VALUES('delete', old.id, old.user_query, old.assistant_response);
+END;
+";
+
+impl HistoryStore {
+    /// Open (creating if needed) the history database at `path` and ensure its schema exists.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open history database: {}", e))?;
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to initialize history schema: {}", e))?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// In-memory fallback used when the on-disk database can't be opened, so a single bad path
+    /// degrades history to "not persisted across restarts" instead of crashing the app.
+    pub fn open_in_memory() -> Result<Self, String> {
+        let conn = Connection::open_in_memory().map_err(|e| format!("Failed to open in-memory history database: {}", e))?;
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to initialize history schema: {}", e))?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// Record one conversation turn.
+    pub fn record_turn(
+        &self,
+        connection_alias: Option<&str>,
+        user_query: &str,
+        assistant_response: &str,
+        execution_time_ms: u64,
+        success: bool,
+    ) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO history (timestamp, connection_alias, user_query, assistant_response, execution_time_ms, success)
+             VALUES (datetime('now'), ?1, ?2, ?3, ?4, ?5)",
+            params![connection_alias, user_query, assistant_response, execution_time_ms as i64, success as i64],
+        )
+        .map_err(|e| format!("Failed to record history turn: {}", e))?;
+        Ok(())
+    }
+
+    /// Full-text search over past turns, optionally scoped to a single connection alias.
+    pub fn search(&self, query: &str, limit: usize, connection_alias: Option<&str>) -> Result<Vec<HistoryEntry>, String> {
+        let fts_query = sanitize_fts_query(query);
+        if fts_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT h.id, h.timestamp, h.connection_alias, h.user_query, h.assistant_response, h.execution_time_ms, h.success
+                 FROM history_fts f JOIN history h ON h.id = f.rowid
+                 WHERE history_fts MATCH ?1 AND (?2 IS NULL OR h.connection_alias = ?2)
+                 ORDER BY h.id DESC LIMIT ?3",
+            )
+            .map_err(|e| format!("Failed to prepare history search: {}", e))?;
+
+        let rows = stmt
+            .query_map(params![fts_query, connection_alias, limit as i64], |row| {
+                Ok(HistoryEntry {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    connection_alias: row.get(2)?,
+                    user_query: row.get(3)?,
+                    assistant_response: row.get(4)?,
+                    execution_time_ms: row.get::<_, i64>(5)? as u64,
+                    success: row.get::<_, i64>(6)? != 0,
+                })
+            })
+            .map_err(|e| format!("Failed to search history: {}", e))?;
+
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| format!("Failed to read history search results: {}", e))
+    }
+
+    /// Total turns, how many succeeded, and the average execution time across all history.
+    pub fn stats(&self) -> Result<HistoryStats, String> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(success), 0), COALESCE(AVG(execution_time_ms), 0.0) FROM history",
+            [],
+            |row| {
+                Ok(HistoryStats {
+                    total_turns: row.get(0)?,
+                    successful_turns: row.get(1)?,
+                    average_execution_time_ms: row.get(2)?,
+                })
+            },
+        )
+        .map_err(|e| format!("Failed to read history stats: {}", e))
+    }
+
+    /// Delete every recorded turn.
+    pub fn clear(&self) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM history", [])
+            .map_err(|e| format!("Failed to clear history: {}", e))?;
+        Ok(())
+    }
+}
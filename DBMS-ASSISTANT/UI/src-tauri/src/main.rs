@@ -1,8 +1,39 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod backend;
 mod commands;
+mod credentials;
+mod db;
+mod history;
+mod policy;
 mod python_bridge;
+mod result_cache;
+mod schema_index;
+
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+/// Initialize the forensic trail as structured JSON lines instead of the old hand-rolled
+/// `"="*80` banners, so both Rust and (via the spans it records) the embedded Python agent
+/// write through a single non-blocking writer rather than racing on the same file handle.
+fn init_tracing(log_dir: &std::path::Path) -> tracing_appender::non_blocking::WorkerGuard {
+    let file_appender = tracing_appender::rolling::never(log_dir, "agent_forensic.log");
+    let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+
+    let filter = EnvFilter::try_from_env("LOG_LEVEL").unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let json_layer = tracing_subscriber::fmt::layer()
+        .json()
+        .with_writer(non_blocking)
+        .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(json_layer)
+        .init();
+
+    guard
+}
 
 fn main() {
     // Load .env file from DBMS-ASSISTANT directory
@@ -10,14 +41,19 @@ fn main() {
     env_path.pop(); // Remove "src-tauri"
     env_path.pop(); // Remove "UI"
     env_path.push(".env");
-    
+
     if env_path.exists() {
         let _ = dotenvy::from_path(&env_path);
     }
-    
+
+    let mut log_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    log_dir.pop(); // Remove "src-tauri"
+    log_dir.pop(); // Remove "UI"
+    let _tracing_guard = init_tracing(&log_dir);
+
     // Initialize Python environment on startup
     let _ = python_bridge::initialize_python();
-    
+
     tauri::Builder::default()
         .manage(commands::AppState::default())
         .setup(|_app| {
@@ -30,9 +66,24 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             commands::greet,
             commands::run_dba_query,
+            commands::run_dba_query_stream,
+            commands::cancel_query,
+            commands::run_orchestrated_query,
             commands::connect_database,
             commands::get_connection_status,
+            commands::introspect_schema,
+            commands::search_schema,
+            commands::register_result_set,
+            commands::query_cached_results,
+            commands::execute_sql,
+            commands::set_policy,
+            commands::get_policy,
             commands::clear_conversation,
+            commands::search_history,
+            commands::history_stats,
+            commands::save_connection_profile,
+            commands::list_profiles,
+            commands::connect_profile,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Coarse classification of a SQL statement, used to look up a `Rule` in the loaded policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementClass {
+    Select,
+    Insert,
+    Update,
+    Delete,
+    Drop,
+    Truncate,
+    Alter,
+    Create,
+    Other,
+}
+
+impl StatementClass {
+    /// Classify a SQL statement by its leading keyword. Deliberately conservative: anything
+    /// it can't confidently classify falls into `Other`, which policies should treat the same
+    /// as the most dangerous class they allow.
+    pub fn classify(sql: &str) -> Self {
+        let first_word = sql
+            .trim_start()
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+
+        match first_word.as_str() {
+            "SELECT" | "WITH" => StatementClass::Select,
+            "INSERT" => StatementClass::Insert,
+            "UPDATE" => StatementClass::Update,
+            "DELETE" => StatementClass::Delete,
+            "DROP" => StatementClass::Drop,
+            "TRUNCATE" => StatementClass::Truncate,
+            "ALTER" => StatementClass::Alter,
+            "CREATE" => StatementClass::Create,
+            _ => StatementClass::Other,
+        }
+    }
+}
+
+/// What to do with a statement of a given class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    Allow,
+    Deny,
+    RequireConfirmation,
+}
+
+/// Allow/deny/require-confirmation rule document. `evaluate` is applied to every statement
+/// run natively (the `execute_sql` command and `NativeSqlBackend`) before it reaches the
+/// database. Statements the agent sends through the MCP tool are not evaluated one by one:
+/// the only part of the policy the MCP server sees is the `READONLY` flag from `is_readonly`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Policy {
+    pub rules: HashMap<StatementClass, Rule>,
+}
+
+impl Policy {
+    /// Only reads are allowed; every write/DDL class is denied outright.
+    pub fn readonly() -> Self {
+        let mut rules = HashMap::new();
+        rules.insert(StatementClass::Select, Rule::Allow);
+        for class in [
+            StatementClass::Insert,
+            StatementClass::Update,
+            StatementClass::Delete,
+            StatementClass::Drop,
+            StatementClass::Truncate,
+            StatementClass::Alter,
+            StatementClass::Create,
+            StatementClass::Other,
+        ] {
+            rules.insert(class, Rule::Deny);
+        }
+        Self { rules }
+    }
+
+    /// Reads and inserts are allowed; updates, deletes, `ALTER`/`CREATE` and unclassified
+    /// statements require explicit confirmation from the UI, and `DROP`/`TRUNCATE` are denied.
+    pub fn standard() -> Self {
+        let mut rules = HashMap::new();
+        rules.insert(StatementClass::Select, Rule::Allow);
+        rules.insert(StatementClass::Insert, Rule::Allow);
+        rules.insert(StatementClass::Update, Rule::RequireConfirmation);
+        rules.insert(StatementClass::Delete, Rule::RequireConfirmation);
+        rules.insert(StatementClass::Alter, Rule::RequireConfirmation);
+        rules.insert(StatementClass::Create, Rule::RequireConfirmation);
+        rules.insert(StatementClass::Drop, Rule::Deny);
+        rules.insert(StatementClass::Truncate, Rule::Deny);
+        rules.insert(StatementClass::Other, Rule::RequireConfirmation);
+        Self { rules }
+    }
+
+    /// Load the effective policy: start from `.env`'s `READONLY` flag, then let an optional
+    /// JSON policy file (`DBMS-ASSISTANT/policy.json`) override individual classes.
+    pub fn load(policy_path: &std::path::Path) -> Self {
+        let mut policy = if std::env::var("READONLY").map(|v| v == "true").unwrap_or(false) {
+            Self::readonly()
+        } else {
+            Self::standard()
+        };
+
+        if let Ok(bytes) = std::fs::read(policy_path) {
+            if let Ok(overrides) = serde_json::from_slice::<HashMap<StatementClass, Rule>>(&bytes) {
+                policy.rules.extend(overrides);
+            }
+        }
+
+        policy
+    }
+
+    pub fn evaluate(&self, sql: &str) -> Rule {
+        let class = StatementClass::classify(sql);
+        *self.rules.get(&class).unwrap_or(&Rule::RequireConfirmation)
+    }
+
+    /// Whether this policy allows no statement class beyond `SELECT` — used to gate the
+    /// `READONLY` flag passed to the MCP server.
+    pub fn is_readonly(&self) -> bool {
+        self.rules
+            .iter()
+            .all(|(class, rule)| *class == StatementClass::Select || *rule != Rule::Allow)
+    }
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Default on-disk location for the optional policy override file.
+pub fn default_policy_path() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.pop(); // Remove "src-tauri"
+    path.pop(); // Remove "UI"
+    path.push("policy.json");
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_leading_keyword_case_insensitively() {
+        assert_eq!(StatementClass::classify("select * from t"), StatementClass::Select);
+        assert_eq!(StatementClass::classify("  DROP TABLE Users"), StatementClass::Drop);
+        assert_eq!(
+            StatementClass::classify("with cte as (select 1) select * from cte"),
+            StatementClass::Select
+        );
+        assert_eq!(StatementClass::classify(""), StatementClass::Other);
+    }
+
+    #[test]
+    fn readonly_policy_denies_everything_but_select() {
+        let policy = Policy::readonly();
+        assert_eq!(policy.evaluate("SELECT 1"), Rule::Allow);
+        assert_eq!(policy.evaluate("DELETE FROM t"), Rule::Deny);
+        assert_eq!(policy.evaluate("DROP TABLE t"), Rule::Deny);
+    }
+
+    #[test]
+    fn standard_policy_requires_confirmation_for_destructive_but_non_dropping_statements() {
+        let policy = Policy::standard();
+        assert_eq!(policy.evaluate("UPDATE t SET x = 1"), Rule::RequireConfirmation);
+        assert_eq!(policy.evaluate("DROP TABLE t"), Rule::Deny);
+        assert_eq!(policy.evaluate("TRUNCATE TABLE t"), Rule::Deny);
+    }
+
+    #[test]
+    fn a_drop_smuggled_behind_a_select_is_allowed_by_the_policy_alone() {
+        // Classification alone calls this a `Select`, so `evaluate` allows it; it's
+        // `ensure_single_statement` (db.rs) that rejects it before it reaches the driver.
+        let policy = Policy::standard();
+        assert_eq!(
+            StatementClass::classify("SELECT 1; DROP TABLE Users;"),
+            StatementClass::Select
+        );
+        assert_eq!(policy.evaluate("SELECT 1; DROP TABLE Users;"), Rule::Allow);
+    }
+}
@@ -1,190 +1,45 @@
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyCFunction, PyDict};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-/// Initialize Python interpreter and set up the environment with venv
-pub fn initialize_python() -> PyResult<()> {
-    Python::with_gil(|py| {
-        // Get paths
-        let mut parent_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        parent_dir.pop(); // Remove "src-tauri"
-        parent_dir.pop(); // Remove "UI"
-        
-        let mut venv_path = parent_dir.clone();
-        venv_path.pop(); // Remove "DBMS-ASSISTANT"
-        venv_path.push(".venv");
-        
-        // Programmatically activate the venv by executing activate_this.py equivalent
-        let activate_code = format!(r#"
-import sys
-import site
-
-# Set venv paths
-venv_path = r'{}'
-site_packages = r'{}'
-
-# Add venv site-packages to the beginning of sys.path
-if site_packages not in sys.path:
-    sys.path.insert(0, site_packages)
-
-# Update sys.prefix and sys.exec_prefix to venv
-sys.prefix = venv_path
-sys.exec_prefix = venv_path
+/// A single piece of agent output emitted while `run_python_query_stream` is still running.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    /// A chunk of assistant text as it is produced.
+    Text { text: String },
+    /// A tool call the agent issued mid-turn (e.g. an MCP SQL execution).
+    ToolCall { name: String },
+}
 
-# Force reload of site to pick up new paths
-import importlib
-importlib.reload(site)
-"#, 
-            venv_path.display(),
-            venv_path.join("lib/python3.13/site-packages").display()
-        );
-        
-        py.run_bound(&activate_code, None, None)?;
-        
-        // Now add project directories to Python path
-        let sys = py.import_bound("sys")?;
-        let path = sys.getattr("path")?;
-        
-        path.call_method1("insert", (0, parent_dir.to_str().unwrap()))?;
-        
-        let project_root = parent_dir.parent().unwrap();
-        path.call_method1("insert", (0, project_root.to_str().unwrap()))?;
-        
-        Ok(())
-    })
+/// Explicit server/database for a single query, overriding the `.env` defaults. Set when the
+/// caller resolved a named connection from `AppState` instead of falling back to whatever
+/// `SERVER_NAME`/`DATABASE_NAME` happen to be configured in the environment.
+#[derive(Debug)]
+pub struct ConnectionTarget {
+    pub server: String,
+    pub database: String,
 }
 
-/// Run a DBA query with conversation history
-pub fn run_python_query_with_history(query: String, history: Vec<(String, String)>) -> PyResult<String> {
-    use std::fs::OpenOptions;
-    use std::io::Write;
-    
-    // Create log file path
-    let mut log_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    log_path.pop(); // Remove "src-tauri"
-    log_path.pop(); // Remove "UI"
-    log_path.push("agent_forensic.log");
-    
-    // Log the incoming query
-    if let Ok(mut log_file) = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_path)
-    {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let _ = writeln!(log_file, "\n{}", "=".repeat(80));
-        let _ = writeln!(log_file, "TIMESTAMP: {}", timestamp);
-        let _ = writeln!(log_file, "INCOMING QUERY: {}", query);
-        let _ = writeln!(log_file, "HISTORY LENGTH: {}", history.len());
-        for (i, (q, r)) in history.iter().enumerate() {
-            let _ = writeln!(log_file, "  History[{}] Q: {}", i, q);
-            let _ = writeln!(log_file, "  History[{}] R: {}", i, r);
-        }
-        let _ = writeln!(log_file, "{}\n", "=".repeat(80));
+/// Python source for the `server`/`database` assignments at the top of `run_query`: a literal
+/// when `target` is set, otherwise the original `os.getenv` fallback.
+fn connection_env_lines(target: &Option<ConnectionTarget>) -> (String, String) {
+    match target {
+        Some(t) => (
+            format!("server = {:?}", t.server),
+            format!("database = {:?}", t.database),
+        ),
+        None => (
+            r#"server = os.getenv("SERVER_NAME", "localhost")"#.to_string(),
+            r#"database = os.getenv("DATABASE_NAME", "master")"#.to_string(),
+        ),
     }
-    
-    Python::with_gil(|py| {
-        // Set __file__ to point to DBMS-ASSISTANT directory first
-        let mut parent_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        parent_dir.pop(); // Remove "src-tauri"
-        parent_dir.pop(); // Remove "UI"
-        let file_path = parent_dir.join("dba_assistant.py");
-        
-        // Get builtins module and add to globals so import works
-        let builtins = py.import_bound("builtins")?;
-        
-        let globals = PyDict::new_bound(py);
-        globals.set_item("__builtins__", builtins)?;
-        globals.set_item("__file__", file_path.to_str().unwrap())?;
-        globals.set_item("__name__", "__main__")?;
-        
-        // Convert history to Python-friendly format
-        let history_str: String = history
-            .iter()
-            .map(|(q, r)| format!("User: {}\nAssistant: {}\n", q, r))
-            .collect::<Vec<_>>()
-            .join("\n---\n");
-        
-        // Create Python code to run async agent query with history
-        let code = format!(r#"
-import asyncio
-import os
-import sys
-from pathlib import Path
-from dotenv import load_dotenv
-
-# Load environment
-env_path = Path(__file__).parent / ".env"
-if env_path.exists():
-    load_dotenv(dotenv_path=env_path, override=True)
-
-from agent_framework import MCPStdioTool
-from agent_framework.azure import AzureAIAgentClient
-from azure.identity.aio import AzureCliCredential
-
-async def run_query():
-    server = os.getenv("SERVER_NAME", "localhost")
-    database = os.getenv("DATABASE_NAME", "master")
-    
-    # Enable debug logging
-    import logging
-    logging.basicConfig(level=logging.DEBUG)
-    logger = logging.getLogger(__name__)
-    
-    # Construct path relative to DBMS-ASSISTANT directory
-    base_path = Path(__file__).parent
-    mcp_server_path = base_path / "MssqlMcp" / "Node" / "dist" / "index.js"
-    
-    if not mcp_server_path.exists():
-        return f"Error: MCP server not found at {{mcp_server_path}}"
-    
-    mcp_env = {{
-        "SERVER_NAME": server,
-        "DATABASE_NAME": database,
-        "SQL_USERNAME": os.getenv("SQL_USERNAME", ""),
-        "SQL_PASSWORD": os.getenv("SQL_PASSWORD", ""),
-        "TRUST_SERVER_CERTIFICATE": os.getenv("TRUST_SERVER_CERTIFICATE", "true"),
-        "READONLY": os.getenv("READONLY", "false"),
-    }}
-    
-    # Previous conversation history
-    history = r'''{history}'''
-    
-    # Build context-aware prompt
-    if history.strip():
-        full_prompt = f"""Previous conversation:
-{{history}}
-
----
-Current question: {query}
+}
 
-Please answer based on the context from our previous conversation."""
-    else:
-        full_prompt = r'''{query}'''
-    
-    result_parts = []
-    chunk_count = 0
-    tool_call_count = 0
-    
-    # Open forensic log for streaming data
-    forensic_log = Path(__file__).parent / "agent_forensic.log"
-    
-    try:
-        async with (
-            AzureCliCredential() as credential,
-            MCPStdioTool(
-                name="mssql",
-                command="node",
-                args=[str(mcp_server_path)],
-                env=mcp_env,
-                description="Microsoft SQL Server database operations",
-            ) as mcp_tool,
-            AzureAIAgentClient(async_credential=credential).create_agent(
-                name="DBA_UI",
-                instructions=f"""You are a helpful SQL Server DBA assistant for server '{{server}}' and database '{{database}}'.
+/// Shared system-prompt instructions for the DBA_UI agent, used by both the
+/// buffered and streaming query paths so the two stay in sync.
+const AGENT_INSTRUCTIONS: &str = r#"You are a helpful SQL Server DBA assistant for server '{server}' and database '{database}'.
 
 You help database administrators with:
 - Health monitoring and diagnostics
@@ -429,44 +284,176 @@ ORDER BY Schema_Name, Table_Name, FK_Name;
 Just execute and deliver results WITH THE ACTUAL DATA. DBAs want action and data, not conversation and summaries.
 
 Always explain your findings clearly and provide actionable recommendations.
-When suggesting SQL queries, ensure they are safe and read-only unless explicitly asked for changes.""",
+When suggesting SQL queries, ensure they are safe and read-only unless explicitly asked for changes.
+"#;
+
+/// Initialize Python interpreter and set up the environment with venv
+pub fn initialize_python() -> PyResult<()> {
+    Python::with_gil(|py| {
+        // Get paths
+        let mut parent_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        parent_dir.pop(); // Remove "src-tauri"
+        parent_dir.pop(); // Remove "UI"
+        
+        let mut venv_path = parent_dir.clone();
+        venv_path.pop(); // Remove "DBMS-ASSISTANT"
+        venv_path.push(".venv");
+        
+        // Programmatically activate the venv by executing activate_this.py equivalent
+        let activate_code = format!(r#"
+import sys
+import site
+
+# Set venv paths
+venv_path = r'{}'
+site_packages = r'{}'
+
+# Add venv site-packages to the beginning of sys.path
+if site_packages not in sys.path:
+    sys.path.insert(0, site_packages)
+
+# Update sys.prefix and sys.exec_prefix to venv
+sys.prefix = venv_path
+sys.exec_prefix = venv_path
+
+# Force reload of site to pick up new paths
+import importlib
+importlib.reload(site)
+"#, 
+            venv_path.display(),
+            venv_path.join("lib/python3.13/site-packages").display()
+        );
+        
+        py.run_bound(&activate_code, None, None)?;
+        
+        // Now add project directories to Python path
+        let sys = py.import_bound("sys")?;
+        let path = sys.getattr("path")?;
+        
+        path.call_method1("insert", (0, parent_dir.to_str().unwrap()))?;
+        
+        let project_root = parent_dir.parent().unwrap();
+        path.call_method1("insert", (0, project_root.to_str().unwrap()))?;
+        
+        Ok(())
+    })
+}
+
+/// Run a DBA query with conversation history inside a `tracing` span (history length,
+/// read-only flag, connection target), logging the query and the response length as
+/// structured JSON events in the forensic log instead of racing hand-written file writes.
+/// Tool calls are not recorded on this buffered path; `run_python_query_stream` records them.
+#[tracing::instrument(skip(query, history), fields(history_len = history.len()))]
+pub fn run_python_query_with_history(
+    query: String,
+    history: Vec<(String, String)>,
+    readonly: bool,
+    target: Option<ConnectionTarget>,
+) -> PyResult<String> {
+    tracing::info!(query = %query, "dispatching query to DBA_UI agent");
+    let (server_line, database_line) = connection_env_lines(&target);
+
+    let result = Python::with_gil(|py| {
+        // Set __file__ to point to DBMS-ASSISTANT directory first
+        let mut parent_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        parent_dir.pop(); // Remove "src-tauri"
+        parent_dir.pop(); // Remove "UI"
+        let file_path = parent_dir.join("dba_assistant.py");
+
+        // Get builtins module and add to globals so import works
+        let builtins = py.import_bound("builtins")?;
+
+        let globals = PyDict::new_bound(py);
+        globals.set_item("__builtins__", builtins)?;
+        globals.set_item("__file__", file_path.to_str().unwrap())?;
+        globals.set_item("__name__", "__main__")?;
+
+        // Convert history to Python-friendly format
+        let history_str: String = history
+            .iter()
+            .map(|(q, r)| format!("User: {}\nAssistant: {}\n", q, r))
+            .collect::<Vec<_>>()
+            .join("\n---\n");
+
+        // Create Python code to run async agent query with history
+        let code = format!(r#"
+import asyncio
+import os
+import sys
+from pathlib import Path
+from dotenv import load_dotenv
+
+# Load environment
+env_path = Path(__file__).parent / ".env"
+if env_path.exists():
+    load_dotenv(dotenv_path=env_path, override=True)
+
+from agent_framework import MCPStdioTool
+from agent_framework.azure import AzureAIAgentClient
+from azure.identity.aio import AzureCliCredential
+
+async def run_query():
+    {server_line}
+    {database_line}
+
+    # Enable debug logging
+    import logging
+    logging.basicConfig(level=logging.DEBUG)
+    logger = logging.getLogger(__name__)
+
+    # Construct path relative to DBMS-ASSISTANT directory
+    base_path = Path(__file__).parent
+    mcp_server_path = base_path / "MssqlMcp" / "Node" / "dist" / "index.js"
+
+    if not mcp_server_path.exists():
+        return f"Error: MCP server not found at {{mcp_server_path}}"
+
+    mcp_env = {{
+        "SERVER_NAME": server,
+        "DATABASE_NAME": database,
+        "SQL_USERNAME": os.getenv("SQL_USERNAME", ""),
+        "SQL_PASSWORD": os.getenv("SQL_PASSWORD", ""),
+        "TRUST_SERVER_CERTIFICATE": os.getenv("TRUST_SERVER_CERTIFICATE", "true"),
+        "READONLY": "{readonly}",
+    }}
+
+    # Previous conversation history
+    history = r'''{history}'''
+
+    # Build context-aware prompt
+    if history.strip():
+        full_prompt = f"""Previous conversation:
+{{history}}
+
+---
+Current question: {query}
+
+Please answer based on the context from our previous conversation."""
+    else:
+        full_prompt = r'''{query}'''
+
+    result_parts = []
+
+    try:
+        async with (
+            AzureCliCredential() as credential,
+            MCPStdioTool(
+                name="mssql",
+                command="node",
+                args=[str(mcp_server_path)],
+                env=mcp_env,
+                description="Microsoft SQL Server database operations",
+            ) as mcp_tool,
+            AzureAIAgentClient(async_credential=credential).create_agent(
+                name="DBA_UI",
+                instructions=f"""{instructions}""",
                 tools=mcp_tool,
             ) as agent,
         ):
-            with open(forensic_log, 'a') as log:
-                log.write(f"\\n[AGENT STREAMING STARTED]\\n")
-                log.write(f"Prompt: {{full_prompt[:200]}}...\\n")
-                log.write("="*80 + "\\n")
-            
             async for chunk in agent.run_stream(full_prompt):
-                # Log ALL chunk attributes
-                with open(forensic_log, 'a') as log:
-                    log.write(f"\\n[RAW CHUNK {{chunk_count + 1}}]\\n")
-                    log.write(f"Chunk type: {{type(chunk)}}\\n")
-                    log.write(f"Chunk dir: {{dir(chunk)}}\\n")
-                    if hasattr(chunk, 'tool_calls'):
-                        log.write(f"Tool calls: {{chunk.tool_calls}}\\n")
-                        tool_call_count += len(chunk.tool_calls or [])
-                    if hasattr(chunk, 'text'):
-                        log.write(f"Text: {{chunk.text}}\\n")
-                    log.write("-"*40 + "\\n")
-                
                 if chunk.text:
-                    chunk_count += 1
                     result_parts.append(chunk.text)
-                    
-                    # Log each text chunk
-                    with open(forensic_log, 'a') as log:
-                        log.write(f"[TEXT CHUNK {{chunk_count}}] Length: {{len(chunk.text)}}\\n")
-                        log.write(f"Content: {{chunk.text}}\\n")
-                        log.write("-"*40 + "\\n")
-            
-            with open(forensic_log, 'a') as log:
-                log.write(f"\\n[STREAMING COMPLETE]\\n")
-                log.write(f"Total text chunks: {{chunk_count}}\\n")
-                log.write(f"Total tool calls detected: {{tool_call_count}}\\n")
-                log.write("="*80 + "\\n")
-        
+
         return ''.join(result_parts) if result_parts else "No response from agent"
     except Exception as e:
         import traceback
@@ -474,44 +461,321 @@ When suggesting SQL queries, ensure they are safe and read-only unless explicitl
 
 # Run the async function
 result = asyncio.run(run_query())
+"#, query = query, history = history_str, instructions = AGENT_INSTRUCTIONS, readonly = readonly,
+            server_line = server_line, database_line = database_line);
 
-# Log the result to forensic file
-import json
-from datetime import datetime
-log_path = Path(__file__).parent / "agent_forensic.log"
-try:
-    with open(log_path, 'a') as f:
-        f.write(f"\\n[PYTHON RESULT AT {{datetime.now().isoformat()}}]\\n")
-        f.write(f"Result length: {{len(result)}} characters\\n")
-        f.write(f"Result preview (first 500 chars): {{result[:500]}}\\n")
-        f.write(f"Result preview (last 500 chars): {{result[-500:]}}\\n")
-        f.write(f"Full result:\\n{{result}}\\n")
-        f.write("="*80 + "\\n")
-except Exception as log_err:
-    pass  # Don't fail if logging fails
-"#, query = query, history = history_str);
-        
         // Execute the code with globals
         py.run_bound(&code, Some(&globals), Some(&globals))?;
-        
+
         // Get the result
         let result: String = globals.get_item("result")?.unwrap().extract()?;
-        
-        // Log the result back in Rust
-        if let Ok(mut log_file) = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&log_path)
-        {
-            let _ = writeln!(log_file, "[RUST RECEIVED RESULT]");
-            let _ = writeln!(log_file, "Result length: {} characters", result.len());
-            let _ = writeln!(log_file, "Result preview (first 200): {}", 
-                if result.len() > 200 { &result[..200] } else { &result });
-            let _ = writeln!(log_file, "Result preview (last 200): {}", 
-                if result.len() > 200 { &result[result.len()-200..] } else { &result });
-            let _ = writeln!(log_file, "{}\n", "=".repeat(80));
+
+        Ok(result)
+    });
+
+    if let Ok(response) = &result {
+        tracing::info!(response_len = response.len(), "DBA_UI agent turn completed");
+    }
+
+    result
+}
+
+/// Run a DBA query with conversation history, invoking `on_event` with each text chunk and
+/// detected tool call as the agent produces them instead of buffering the whole turn. Each
+/// chunk/tool call is also recorded as a `tracing` event under this call's span.
+#[tracing::instrument(skip(query, history, on_event), fields(history_len = history.len()))]
+pub fn run_python_query_stream(
+    query: String,
+    history: Vec<(String, String)>,
+    on_event: impl Fn(StreamEvent) + Send + 'static,
+    readonly: bool,
+    target: Option<ConnectionTarget>,
+) -> PyResult<String> {
+    tracing::info!(query = %query, "dispatching streaming query to DBA_UI agent");
+    let (server_line, database_line) = connection_env_lines(&target);
+
+    let on_event = move |event: StreamEvent| {
+        match &event {
+            StreamEvent::Text { text } => tracing::debug!(chunk_len = text.len(), "streamed text chunk"),
+            StreamEvent::ToolCall { name } => tracing::debug!(tool = %name, "agent tool call"),
         }
-        
+        on_event(event);
+    };
+
+    let result = Python::with_gil(|py| {
+        // Set __file__ to point to DBMS-ASSISTANT directory first
+        let mut parent_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        parent_dir.pop(); // Remove "src-tauri"
+        parent_dir.pop(); // Remove "UI"
+        let file_path = parent_dir.join("dba_assistant.py");
+
+        // Get builtins module and add to globals so import works
+        let builtins = py.import_bound("builtins")?;
+
+        let globals = PyDict::new_bound(py);
+        globals.set_item("__builtins__", builtins)?;
+        globals.set_item("__file__", file_path.to_str().unwrap())?;
+        globals.set_item("__name__", "__main__")?;
+
+        // Expose the Rust callback to Python as a plain global callable. Called once per
+        // streamed text chunk or detected tool call from inside the `async for chunk` loop.
+        let emit = PyCFunction::new_closure_bound(
+            py,
+            Some("__emit_event"),
+            None,
+            move |args, _kwargs| -> PyResult<()> {
+                let event_type: String = args.get_item(0)?.extract()?;
+                let payload: String = args.get_item(1)?.extract()?;
+                let event = match event_type.as_str() {
+                    "tool_call" => StreamEvent::ToolCall { name: payload },
+                    _ => StreamEvent::Text { text: payload },
+                };
+                on_event(event);
+                Ok(())
+            },
+        )?;
+        globals.set_item("__emit_event", emit)?;
+
+        // Convert history to Python-friendly format
+        let history_str: String = history
+            .iter()
+            .map(|(q, r)| format!("User: {}\nAssistant: {}\n", q, r))
+            .collect::<Vec<_>>()
+            .join("\n---\n");
+
+        // Same agent setup as `run_python_query_with_history`, but the streaming loop calls
+        // back into Rust via `__emit_event` as each chunk arrives instead of only appending
+        // to `result_parts`.
+        let code = format!(r#"
+import asyncio
+import os
+from pathlib import Path
+from dotenv import load_dotenv
+
+# Load environment
+env_path = Path(__file__).parent / ".env"
+if env_path.exists():
+    load_dotenv(dotenv_path=env_path, override=True)
+
+from agent_framework import MCPStdioTool
+from agent_framework.azure import AzureAIAgentClient
+from azure.identity.aio import AzureCliCredential
+
+async def run_query():
+    {server_line}
+    {database_line}
+
+    base_path = Path(__file__).parent
+    mcp_server_path = base_path / "MssqlMcp" / "Node" / "dist" / "index.js"
+
+    if not mcp_server_path.exists():
+        return f"Error: MCP server not found at {{mcp_server_path}}"
+
+    mcp_env = {{
+        "SERVER_NAME": server,
+        "DATABASE_NAME": database,
+        "SQL_USERNAME": os.getenv("SQL_USERNAME", ""),
+        "SQL_PASSWORD": os.getenv("SQL_PASSWORD", ""),
+        "TRUST_SERVER_CERTIFICATE": os.getenv("TRUST_SERVER_CERTIFICATE", "true"),
+        "READONLY": "{readonly}",
+    }}
+
+    history = r'''{history}'''
+
+    if history.strip():
+        full_prompt = f"""Previous conversation:
+{{history}}
+
+---
+Current question: {query}
+
+Please answer based on the context from our previous conversation."""
+    else:
+        full_prompt = r'''{query}'''
+
+    result_parts = []
+
+    try:
+        async with (
+            AzureCliCredential() as credential,
+            MCPStdioTool(
+                name="mssql",
+                command="node",
+                args=[str(mcp_server_path)],
+                env=mcp_env,
+                description="Microsoft SQL Server database operations",
+            ) as mcp_tool,
+            AzureAIAgentClient(async_credential=credential).create_agent(
+                name="DBA_UI",
+                instructions=f"""{instructions}""",
+                tools=mcp_tool,
+            ) as agent,
+        ):
+            async for chunk in agent.run_stream(full_prompt):
+                if chunk.text:
+                    result_parts.append(chunk.text)
+                    __emit_event("text", chunk.text)
+                for tool_call in (getattr(chunk, "tool_calls", None) or []):
+                    __emit_event("tool_call", str(getattr(tool_call, "name", tool_call)))
+
+        return ''.join(result_parts) if result_parts else "No response from agent"
+    except Exception as e:
+        import traceback
+        return f"Error: {{str(e)}}\n\nTraceback:\n{{traceback.format_exc()}}"
+
+result = asyncio.run(run_query())
+"#, query = query, history = history_str, instructions = AGENT_INSTRUCTIONS, readonly = readonly,
+            server_line = server_line, database_line = database_line);
+
+        py.run_bound(&code, Some(&globals), Some(&globals))?;
+
+        let result: String = globals.get_item("result")?.unwrap().extract()?;
+        Ok(result)
+    });
+
+    if let Ok(response) = &result {
+        tracing::info!(response_len = response.len(), "DBA_UI agent streaming turn completed");
+    }
+
+    result
+}
+
+/// Run a request through a small team of specialized agents — a Planner that decomposes the
+/// request into steps, a SQL-Executor bound to the MCP tool, and a Diagram agent that only
+/// emits Mermaid `erDiagram` text — instead of one monolithic agent juggling all three roles
+/// at once. The coordinator dispatches each planned step and feeds every agent the schema and
+/// prior results discovered so far.
+#[tracing::instrument(skip(query, history), fields(history_len = history.len()))]
+pub fn run_orchestrated_query(
+    query: String,
+    history: Vec<(String, String)>,
+    readonly: bool,
+    target: Option<ConnectionTarget>,
+) -> PyResult<String> {
+    tracing::info!(query = %query, "dispatching orchestrated query to planner/executor/diagram team");
+    let (server_line, database_line) = connection_env_lines(&target);
+
+    Python::with_gil(|py| {
+        let mut parent_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        parent_dir.pop(); // Remove "src-tauri"
+        parent_dir.pop(); // Remove "UI"
+        let file_path = parent_dir.join("dba_assistant.py");
+
+        let builtins = py.import_bound("builtins")?;
+
+        let globals = PyDict::new_bound(py);
+        globals.set_item("__builtins__", builtins)?;
+        globals.set_item("__file__", file_path.to_str().unwrap())?;
+        globals.set_item("__name__", "__main__")?;
+
+        let history_str: String = history
+            .iter()
+            .map(|(q, r)| format!("User: {}\nAssistant: {}\n", q, r))
+            .collect::<Vec<_>>()
+            .join("\n---\n");
+
+        let code = format!(r#"
+import asyncio
+import json
+import os
+from pathlib import Path
+from dotenv import load_dotenv
+
+env_path = Path(__file__).parent / ".env"
+if env_path.exists():
+    load_dotenv(dotenv_path=env_path, override=True)
+
+from agent_framework import MCPStdioTool
+from agent_framework.azure import AzureAIAgentClient
+from azure.identity.aio import AzureCliCredential
+
+PLANNER_INSTRUCTIONS = """You are the Planner for a SQL Server DBA assistant team. Decompose
+the user's request into an ordered list of steps, each tagged with the role that should
+execute it: "sql" for anything requiring schema discovery or a SQL query, or "diagram" for
+anything requiring a Mermaid ERD. Respond with ONLY a JSON array of objects shaped like
+{{"role": "sql" | "diagram", "instruction": "..."}}. Keep steps small and concrete."""
+
+SQL_EXECUTOR_INSTRUCTIONS = """You are the SQL-Executor for a SQL Server DBA assistant team,
+for server '{{server}}' and database '{{database}}'. You have access to the mssql MCP tools.
+Verify table and column names against INFORMATION_SCHEMA before writing SQL, show the actual
+rows you retrieve, and do not ask clarifying questions — execute the step you were given."""
+
+DIAGRAM_INSTRUCTIONS = """You are the Diagram agent for a SQL Server DBA assistant team. You
+only ever respond with Mermaid `erDiagram` syntax in a fenced ```mermaid code block, built from
+the foreign-key relationships you are given in context. Never execute Python or generate image
+files."""
+
+async def run_orchestrated():
+    {server_line}
+    {database_line}
+
+    base_path = Path(__file__).parent
+    mcp_server_path = base_path / "MssqlMcp" / "Node" / "dist" / "index.js"
+    if not mcp_server_path.exists():
+        return f"Error: MCP server not found at {{mcp_server_path}}"
+
+    mcp_env = {{
+        "SERVER_NAME": server,
+        "DATABASE_NAME": database,
+        "SQL_USERNAME": os.getenv("SQL_USERNAME", ""),
+        "SQL_PASSWORD": os.getenv("SQL_PASSWORD", ""),
+        "TRUST_SERVER_CERTIFICATE": os.getenv("TRUST_SERVER_CERTIFICATE", "true"),
+        "READONLY": "{readonly}",
+    }}
+
+    history = r'''{history}'''
+    request = r'''{query}'''
+
+    shared_context = [f"Previous conversation:\\n{{history}}"] if history.strip() else []
+
+    try:
+        async with (
+            AzureCliCredential() as credential,
+            MCPStdioTool(
+                name="mssql",
+                command="node",
+                args=[str(mcp_server_path)],
+                env=mcp_env,
+                description="Microsoft SQL Server database operations",
+            ) as mcp_tool,
+        ):
+            client = AzureAIAgentClient(async_credential=credential)
+
+            async with (
+                client.create_agent(name="Planner", instructions=PLANNER_INSTRUCTIONS) as planner,
+                client.create_agent(name="SQL-Executor", instructions=SQL_EXECUTOR_INSTRUCTIONS.format(server=server, database=database), tools=mcp_tool) as sql_executor,
+                client.create_agent(name="Diagram", instructions=DIAGRAM_INSTRUCTIONS) as diagram_agent,
+            ):
+                plan_response = await planner.run(request)
+                try:
+                    steps = json.loads(str(plan_response))
+                except (json.JSONDecodeError, TypeError):
+                    steps = [{{"role": "sql", "instruction": request}}]
+
+                outputs = []
+                for step in steps:
+                    role = step.get("role", "sql")
+                    instruction = step.get("instruction", request)
+                    context = "\\n\\n".join(shared_context)
+                    prompt = f"{{context}}\\n\\nStep: {{instruction}}" if context else instruction
+
+                    agent = diagram_agent if role == "diagram" else sql_executor
+                    step_result = str(await agent.run(prompt))
+                    outputs.append(step_result)
+                    shared_context.append(step_result)
+
+                return "\\n\\n".join(outputs) if outputs else "No response from orchestrated team"
+    except Exception as e:
+        import traceback
+        return f"Error: {{str(e)}}\\n\\nTraceback:\\n{{traceback.format_exc()}}"
+
+result = asyncio.run(run_orchestrated())
+"#, query = query, history = history_str, readonly = readonly,
+            server_line = server_line, database_line = database_line);
+
+        py.run_bound(&code, Some(&globals), Some(&globals))?;
+
+        let result: String = globals.get_item("result")?.unwrap().extract()?;
         Ok(result)
     })
 }
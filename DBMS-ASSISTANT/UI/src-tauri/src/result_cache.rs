@@ -0,0 +1,84 @@
+use datafusion::arrow::json::reader::infer_json_schema_from_seekable;
+use datafusion::arrow::json::ReaderBuilder;
+use datafusion::datasource::MemTable;
+use datafusion::prelude::*;
+use serde_json::Value;
+use std::io::{Cursor, Seek};
+use std::sync::Arc;
+
+/// Caches structured result sets returned by the agent in-process so a follow-up question
+/// ("now filter those to revenue > 10M") can be answered with a plain SQL query over
+/// already-fetched rows instead of another agent + MCP round-trip.
+#[derive(Clone)]
+pub struct ResultCache {
+    ctx: SessionContext,
+}
+
+impl Default for ResultCache {
+    fn default() -> Self {
+        Self {
+            ctx: SessionContext::new(),
+        }
+    }
+}
+
+impl ResultCache {
+    /// Register a result set (one JSON object per row) as an in-memory table, keyed by the
+    /// name the agent assigned it. Re-registering a name replaces the previous table. The
+    /// table's schema is inferred from the rows themselves.
+    pub async fn register_result_set(&self, name: &str, rows: Vec<Value>) -> Result<(), String> {
+        let ndjson: String = rows
+            .iter()
+            .map(|row| row.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut cursor = Cursor::new(ndjson.into_bytes());
+
+        let (schema, _) = infer_json_schema_from_seekable(&mut cursor, None)
+            .map_err(|e| format!("Failed to infer schema for '{}': {}", name, e))?;
+        let schema = Arc::new(schema);
+        cursor.rewind().map_err(|e| format!("Failed to rewind rows for '{}': {}", name, e))?;
+
+        let mut reader = ReaderBuilder::new(schema.clone())
+            .build(cursor)
+            .map_err(|e| format!("Failed to build JSON reader for '{}': {}", name, e))?;
+
+        let mut batches = Vec::new();
+        while let Some(batch) = reader.next() {
+            batches.push(batch.map_err(|e| format!("Failed to decode rows for '{}': {}", name, e))?);
+        }
+
+        let table = MemTable::try_new(schema, vec![batches])
+            .map_err(|e| format!("Failed to build in-memory table for '{}': {}", name, e))?;
+
+        self.ctx
+            .deregister_table(name)
+            .map_err(|e| format!("Failed to clear existing table '{}': {}", name, e))?;
+        self.ctx
+            .register_table(name, Arc::new(table))
+            .map_err(|e| format!("Failed to register table '{}': {}", name, e))?;
+
+        Ok(())
+    }
+
+    /// Run plain SQL over the cached result sets and return the rows as JSON.
+    pub async fn query(&self, sql: &str) -> Result<Vec<Value>, String> {
+        let df = self
+            .ctx
+            .sql(sql)
+            .await
+            .map_err(|e| format!("Failed to plan query: {}", e))?;
+
+        let batches = df.collect().await.map_err(|e| format!("Failed to execute query: {}", e))?;
+
+        let buf = Vec::new();
+        let mut writer = datafusion::arrow::json::ArrayWriter::new(buf);
+        writer
+            .write_batches(&batches.iter().collect::<Vec<_>>())
+            .map_err(|e| format!("Failed to serialize results: {}", e))?;
+        writer.finish().map_err(|e| format!("Failed to serialize results: {}", e))?;
+
+        let json_bytes = writer.into_inner();
+        serde_json::from_slice(&json_bytes).map_err(|e| format!("Failed to parse serialized results: {}", e))
+    }
+}
@@ -0,0 +1,159 @@
+use instant_distance::{Builder, HnswMap, Point, Search};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::db::SchemaSnapshot;
+
+/// One retrievable schema fact: a table or a column, described in natural language so it can
+/// be embedded and matched against a natural-language question.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaEntry {
+    /// Schema-qualified name, e.g. `dim.DimCustomer` or `dim.DimCustomer.CompanyName`.
+    pub label: String,
+    /// Name + data type + FK context fed to the embedding model.
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Embedding(Vec<f32>);
+
+impl Point for Embedding {
+    fn distance(&self, other: &Self) -> f32 {
+        let dot: f32 = self.0.iter().zip(&other.0).map(|(a, b)| a * b).sum();
+        let norm_a = self.0.iter().map(|a| a * a).sum::<f32>().sqrt();
+        let norm_b = other.0.iter().map(|b| b * b).sum::<f32>().sqrt();
+        1.0 - dot / (norm_a * norm_b + f32::EPSILON)
+    }
+}
+
+/// Local HNSW index over schema embeddings, persisted under the DBMS-ASSISTANT dir so the
+/// agent can retrieve just the relevant tables/columns for a question instead of being handed
+/// (or re-fetching) the entire catalog every turn.
+pub struct SchemaIndex {
+    map: HnswMap<Embedding, SchemaEntry>,
+    index_path: PathBuf,
+}
+
+/// Default on-disk location for the persisted index: `schema_index.bin` under the
+/// DBMS-ASSISTANT directory, alongside `agent_forensic.log` and `.env`.
+pub fn default_index_path() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.pop(); // Remove "src-tauri"
+    path.pop(); // Remove "UI"
+    path.push("schema_index.bin");
+    path
+}
+
+impl SchemaIndex {
+    /// Build descriptions for every table/column in `snapshot`, embed them via the configured
+    /// Azure embedding deployment, and persist the resulting index to `index_path`.
+    pub async fn build(snapshot: &SchemaSnapshot, index_path: PathBuf) -> Result<Self, String> {
+        let mut entries = Vec::new();
+
+        for table in &snapshot.tables {
+            entries.push(SchemaEntry {
+                label: table.clone(),
+                description: format!("Table {}", table),
+            });
+        }
+
+        for column in &snapshot.columns {
+            let fk = snapshot
+                .foreign_keys
+                .iter()
+                .find(|fk| fk.schema == column.schema && fk.table == column.table && fk.column == column.column);
+            let fk_context = fk
+                .map(|fk| format!(", references {}.{}.{}", fk.referenced_schema, fk.referenced_table, fk.referenced_column))
+                .unwrap_or_default();
+
+            entries.push(SchemaEntry {
+                label: format!("{}.{}.{}", column.schema, column.table, column.column),
+                description: format!(
+                    "Column {} on table {}.{}, type {}{}",
+                    column.column, column.schema, column.table, column.data_type, fk_context
+                ),
+            });
+        }
+
+        let mut points = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            points.push(Embedding(embed(&entry.description).await?));
+        }
+
+        let map = Builder::default().build(points, entries);
+
+        let index = Self { map, index_path };
+        index.persist()?;
+        Ok(index)
+    }
+
+    /// Load a previously persisted index, if one exists and hasn't been invalidated.
+    pub fn load(index_path: &Path) -> Option<Self> {
+        let bytes = fs::read(index_path).ok()?;
+        let map: HnswMap<Embedding, SchemaEntry> = bincode::deserialize(&bytes).ok()?;
+        Some(Self {
+            map,
+            index_path: index_path.to_path_buf(),
+        })
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let bytes = bincode::serialize(&self.map).map_err(|e| format!("Failed to serialize schema index: {}", e))?;
+        fs::write(&self.index_path, bytes).map_err(|e| format!("Failed to write schema index: {}", e))
+    }
+
+    /// Invalidate the on-disk index, e.g. when introspection detects schema drift.
+    pub fn invalidate(index_path: &Path) {
+        let _ = fs::remove_file(index_path);
+    }
+
+    /// Return the `top_k` tables/columns whose description is most relevant to `query`.
+    pub async fn search(&self, query: &str, top_k: usize) -> Result<Vec<SchemaEntry>, String> {
+        let point = Embedding(embed(query).await?);
+        let mut search = Search::default();
+        Ok(self
+            .map
+            .search(&point, &mut search)
+            .take(top_k)
+            .map(|item| item.value.clone())
+            .collect())
+    }
+}
+
+/// Embed a piece of text using the Azure OpenAI embedding deployment configured via `.env`.
+async fn embed(text: &str) -> Result<Vec<f32>, String> {
+    let endpoint = std::env::var("AZURE_OPENAI_ENDPOINT").map_err(|_| "AZURE_OPENAI_ENDPOINT not set".to_string())?;
+    let deployment = std::env::var("AZURE_OPENAI_EMBEDDING_DEPLOYMENT")
+        .map_err(|_| "AZURE_OPENAI_EMBEDDING_DEPLOYMENT not set".to_string())?;
+    let api_key = std::env::var("AZURE_OPENAI_API_KEY").map_err(|_| "AZURE_OPENAI_API_KEY not set".to_string())?;
+    let api_version = std::env::var("AZURE_OPENAI_API_VERSION").unwrap_or_else(|_| "2024-06-01".to_string());
+
+    let url = format!(
+        "{}/openai/deployments/{}/embeddings?api-version={}",
+        endpoint.trim_end_matches('/'),
+        deployment,
+        api_version
+    );
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(&url)
+        .header("api-key", api_key)
+        .json(&serde_json::json!({ "input": text }))
+        .send()
+        .await
+        .map_err(|e| format!("Embedding request failed: {}", e))?;
+
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse embedding response: {}", e))?;
+
+    body["data"][0]["embedding"]
+        .as_array()
+        .ok_or_else(|| "Embedding response missing 'data[0].embedding'".to_string())?
+        .iter()
+        .map(|v| v.as_f64().map(|f| f as f32).ok_or_else(|| "Non-numeric embedding value".to_string()))
+        .collect()
+}